@@ -1,28 +1,122 @@
+use async_openai::config::OpenAIConfig;
 use async_openai::types::AudioInput;
 use async_openai::{types::CreateTranscriptionRequestArgs, Client};
+use clap::Parser;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use ringbuf::traits::{Consumer, Producer, Split};
+use ringbuf::{HeapProd, HeapRb};
+use rubato::{
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
 use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Sample rate and channel layout Whisper expects its input audio in.
+const TARGET_SAMPLE_RATE: u32 = 16_000;
+const TARGET_CHANNELS: usize = 1;
+/// Number of input frames fed to the resampler at a time.
+const RESAMPLE_CHUNK_FRAMES: usize = 1024;
+
+/// RMS level below which a buffer is treated as silence.
+const DEFAULT_SILENCE_THRESHOLD: f32 = 0.02;
+/// How long contiguous silence has to last (after speech was heard) before
+/// recording stops automatically.
+const DEFAULT_SILENCE_HANGOVER: Duration = Duration::from_millis(800);
+
+/// How often the continuous dictation loop drains the ring buffer and fires
+/// off a transcription request.
+const CONTINUOUS_CHUNK_SECONDS: f32 = 5.0;
+/// Trailing audio carried over into the next chunk so words split across a
+/// chunk boundary aren't lost.
+const CONTINUOUS_OVERLAP_SECONDS: f32 = 0.5;
+/// Ring buffer capacity, generous enough to absorb a slow transcription
+/// round-trip without dropping audio.
+const CONTINUOUS_RING_SECONDS: usize = 30;
+
+/// Command-line options for picking which audio hardware to record from.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// List all available input devices for the chosen host and exit.
+    #[arg(long)]
+    list_devices: bool,
+
+    /// Name (or substring) of the input device to record from. Defaults to
+    /// the host's default input device.
+    #[arg(long)]
+    device: Option<String>,
+
+    /// Host backend to use, e.g. "jack" or "coreaudio". Defaults to cpal's
+    /// default host for this platform.
+    #[arg(long)]
+    host: Option<String>,
+
+    /// RMS level (0.0-1.0) below which a buffer counts as silence.
+    #[arg(long, default_value_t = DEFAULT_SILENCE_THRESHOLD)]
+    silence_threshold: f32,
+
+    /// How long contiguous silence must last before recording stops, in
+    /// milliseconds.
+    #[arg(long, default_value_t = DEFAULT_SILENCE_HANGOVER.as_millis() as u64)]
+    silence_hangover_ms: u64,
+
+    /// Keep listening and transcribing rolling chunks until interrupted,
+    /// instead of recording one file and exiting.
+    #[arg(long)]
+    continuous: bool,
+
+    /// Save the recording to this WAV file instead of transcribing straight
+    /// from memory.
+    #[arg(long)]
+    output: Option<std::path::PathBuf>,
+
+    /// Play back an existing WAV file through the default output device
+    /// instead of recording.
+    #[arg(long)]
+    play: Option<std::path::PathBuf>,
+
+    /// Loopback self-test: record, then immediately play the recording back
+    /// instead of transcribing it. Useful for checking device/sample-format
+    /// issues before they show up as garbled transcripts.
+    #[arg(long)]
+    loopback: bool,
+}
+
+/// Resolves a `cpal::Host` by backend name (e.g. "jack" or "coreaudio"),
+/// falling back to cpal's platform default when `host_id` is `None`.
+fn resolve_host(host_id: Option<&str>) -> anyhow::Result<cpal::Host> {
+    match host_id {
+        Some(id) => Ok(cpal::host_from_id(
+            cpal::available_hosts()
+                .into_iter()
+                .find(|h| h.name().eq_ignore_ascii_case(id))
+                .ok_or_else(|| anyhow::anyhow!("Unknown host '{id}'"))?,
+        )?),
+        None => Ok(cpal::default_host()),
+    }
+}
+
 struct InputDevice {
     pub host: cpal::Host,
     pub device: cpal::Device,
     pub config: cpal::SupportedStreamConfig,
 }
 
-// The WAV file we're recording to.
-const PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/recorded.wav");
-
 impl InputDevice {
-    pub fn new() -> anyhow::Result<Self> {
-        let host = cpal::default_host();
+    pub fn new(device_name: Option<&str>, host_id: Option<&str>) -> anyhow::Result<Self> {
+        let host = resolve_host(host_id)?;
 
-        let device = host
-            .default_input_device()
-            // TODO: replace unwrap later
-            .ok_or("No input device available")
-            .unwrap();
+        let device = match device_name {
+            Some(name) => host
+                .input_devices()?
+                .find(|d| d.name().map(|n| n.contains(name)).unwrap_or(false))
+                .ok_or_else(|| anyhow::anyhow!("No input device matching '{name}'"))?,
+            None => host
+                .default_input_device()
+                .ok_or_else(|| anyhow::anyhow!("No input device available"))?,
+        };
 
         let config = device.default_input_config()?;
 
@@ -37,25 +131,66 @@ impl InputDevice {
     }
 }
 
+/// Print the name of every input device available on `host`.
+fn list_input_devices(host: &cpal::Host) -> anyhow::Result<()> {
+    println!("Input devices on host \"{}\":", host.id().name());
+    for (i, device) in host.input_devices()?.enumerate() {
+        println!("  {}: {}", i, device.name()?);
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let input_device = InputDevice::new()?;
+    let args = Args::parse();
 
-    let spec = wav_spec_from_config(&input_device.config);
-    let writer = hound::WavWriter::create(PATH, spec)?;
-    let writer = Arc::new(Mutex::new(Some(writer)));
-    // A flag to indicate that recording is in progress.
-    println!("Begin recording...");
+    if args.list_devices {
+        let host = resolve_host(args.host.as_deref())?;
+        list_input_devices(&host)?;
+        return Ok(());
+    }
 
-    // Run the input stream on a separate thread.
-    let writer_2 = writer.clone();
+    if let Some(path) = &args.play {
+        let host = resolve_host(args.host.as_deref())?;
+        play_wav_bytes(&std::fs::read(path)?, &host)?;
+        return Ok(());
+    }
 
-    write_mic_audio_to_file(writer_2, &input_device, Duration::from_secs(5))?;
+    let input_device = InputDevice::new(args.device.as_deref(), args.host.as_deref())?;
 
-    let filename = PATH.to_string();
-    let file_contents = std::fs::read(filename)?;
+    if args.continuous {
+        run_continuous_dictation(&input_device).await?;
+        return Ok(());
+    }
 
-    let audio_input = AudioInput::from_vec_u8(PATH.to_string(), file_contents);
+    let spec = target_wav_spec();
+    let vad_config = VadConfig {
+        silence_threshold: args.silence_threshold,
+        silence_hangover: Duration::from_millis(args.silence_hangover_ms),
+    };
+    println!("Begin recording...");
+
+    let (audio_filename, file_contents) = match &args.output {
+        Some(path) => {
+            let writer = hound::WavWriter::create(path, spec)?;
+            let writer = Arc::new(Mutex::new(Some(writer)));
+            write_mic_audio_to_file(writer, &input_device, vad_config)?;
+            (path.display().to_string(), std::fs::read(path)?)
+        }
+        None => {
+            let cursor = SharedCursor::new();
+            let writer = hound::WavWriter::new(cursor.clone(), spec)?;
+            let writer = Arc::new(Mutex::new(Some(writer)));
+            write_mic_audio_to_file(writer, &input_device, vad_config)?;
+            ("recording.wav".to_string(), cursor.into_bytes())
+        }
+    };
+
+    if args.loopback {
+        return play_wav_bytes(&file_contents, &input_device.host).map_err(Into::into);
+    }
+
+    let audio_input = AudioInput::from_vec_u8(audio_filename, file_contents);
     let client = Client::new();
     let request = CreateTranscriptionRequestArgs::default()
         .file(audio_input)
@@ -63,51 +198,65 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .build()?;
     let response = client.audio().transcribe(request).await?;
     println!("{}", response.text);
-    std::fs::remove_file(PATH)?;
     Ok(())
 }
 
-fn write_mic_audio_to_file(
-    wav_file_writer: Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>>>,
+/// Parameters for the energy-based voice-activity stop condition.
+struct VadConfig {
+    silence_threshold: f32,
+    silence_hangover: Duration,
+}
+
+fn write_mic_audio_to_file<W>(
+    wav_file_writer: WavWriterHandle<W>,
     input_device: &InputDevice,
-    duration: Duration,
-) -> Result<(), anyhow::Error> {
+    vad_config: VadConfig,
+) -> Result<(), anyhow::Error>
+where
+    W: std::io::Write + std::io::Seek + Send + 'static,
+{
     let (_, device, config) = input_device.get_all();
 
     let sample_format = config.sample_format();
+    let channels = config.channels() as usize;
 
     println!("sample format {sample_format:?}");
 
-    // let stream_data = Arc::new(Mutex::new(Vec::new()));
-    // let stream_data_clone = Arc::clone(&stream_data);
-
     let err_fn = |err| eprintln!("An error occurred on the audio stream: {}", err);
 
-    let wav_file_writer = wav_file_writer.clone();
-    let wav_file_writer_clone = wav_file_writer.clone();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+
+    let resample_state = Arc::new(Mutex::new(ResampleState::new(
+        wav_file_writer.clone(),
+        config.sample_rate().0,
+        channels,
+        vad_config,
+        stop_flag.clone(),
+    )?));
+    let resample_state_clone = resample_state.clone();
 
     let stream = match config.sample_format() {
         cpal::SampleFormat::I8 => device.build_input_stream(
             &config.to_owned().into(),
-            move |data, _: &_| write_input_data::<i8, i8>(data, &wav_file_writer),
+            move |data, _: &_| write_input_data::<i8, _>(data, &resample_state),
             err_fn,
             None,
         )?,
         cpal::SampleFormat::I16 => device.build_input_stream(
             &config.to_owned().into(),
-            move |data, _: &_| write_input_data::<i16, i16>(data, &wav_file_writer),
+            move |data, _: &_| write_input_data::<i16, _>(data, &resample_state),
             err_fn,
             None,
         )?,
         cpal::SampleFormat::I32 => device.build_input_stream(
             &config.to_owned().into(),
-            move |data, _: &_| write_input_data::<i32, i32>(data, &wav_file_writer),
+            move |data, _: &_| write_input_data::<i32, _>(data, &resample_state),
             err_fn,
             None,
         )?,
         cpal::SampleFormat::F32 => device.build_input_stream(
             &config.to_owned().into(),
-            move |data, _: &_| write_input_data::<f32, f32>(data, &wav_file_writer),
+            move |data, _: &_| write_input_data::<f32, _>(data, &resample_state),
             err_fn,
             None,
         )?,
@@ -120,49 +269,492 @@ fn write_mic_audio_to_file(
 
     stream.play()?;
 
-    // Let recording go for roughly three seconds.
-    std::thread::sleep(duration);
+    println!("Listening for speech, will stop after a period of silence...");
+    while !stop_flag.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
     drop(stream);
-    wav_file_writer_clone
-        .lock()
-        .unwrap()
-        .take()
-        .unwrap()
-        .finalize()?;
-    println!("Recording {} complete!", PATH);
+    resample_state_clone.lock().unwrap().flush()?;
+    wav_file_writer.lock().unwrap().take().unwrap().finalize()?;
+    println!("Recording complete!");
     Ok(())
 }
 
-fn wav_spec_from_config(config: &cpal::SupportedStreamConfig) -> hound::WavSpec {
+/// The WAV spec we always write out: Whisper wants 16 kHz mono 16-bit PCM,
+/// regardless of what the input device natively captures at.
+fn target_wav_spec() -> hound::WavSpec {
     hound::WavSpec {
-        channels: config.channels() as _,
-        sample_rate: config.sample_rate().0 as _,
-        bits_per_sample: (config.sample_format().sample_size() * 8) as _,
-        sample_format: sample_format(config.sample_format()),
+        channels: TARGET_CHANNELS as u16,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
     }
 }
 
-fn sample_format(format: cpal::SampleFormat) -> hound::SampleFormat {
-    if format.is_float() {
-        hound::SampleFormat::Float
-    } else {
-        hound::SampleFormat::Int
+type WavWriterHandle<W> = Arc<Mutex<Option<hound::WavWriter<W>>>>;
+
+/// A `Vec<u8>`-backed, `Clone`-able `Write + Seek` sink. Lets a
+/// `hound::WavWriter` build a WAV entirely in memory while we keep a handle
+/// to read the bytes back out after `finalize()` consumes the writer.
+#[derive(Clone)]
+struct SharedCursor(Arc<Mutex<std::io::Cursor<Vec<u8>>>>);
+
+impl SharedCursor {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(std::io::Cursor::new(Vec::new()))))
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.0.lock().unwrap().get_ref().clone()
     }
 }
 
-type WavWriterHandle = Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>>>;
+impl std::io::Write for SharedCursor {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
 
-fn write_input_data<T, U>(input: &[T], writer: &WavWriterHandle)
+impl std::io::Seek for SharedCursor {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.0.lock().unwrap().seek(pos)
+    }
+}
+
+/// Downmixes interleaved input down to mono and resamples it to 16 kHz.
+/// Shared by the one-shot file recorder and the continuous dictation loop.
+struct PcmResampler {
+    resampler: SincFixedIn<f32>,
+    channels: usize,
+    /// Mono samples at the input sample rate, awaiting a full resampler chunk.
+    pending: Vec<f32>,
+}
+
+impl PcmResampler {
+    fn new(input_sample_rate: u32, channels: usize) -> anyhow::Result<Self> {
+        let params = SincInterpolationParameters {
+            sinc_len: 256,
+            f_cutoff: 0.95,
+            interpolation: SincInterpolationType::Cubic,
+            oversampling_factor: 256,
+            window: WindowFunction::BlackmanHarris2,
+        };
+        let resample_ratio = TARGET_SAMPLE_RATE as f64 / input_sample_rate as f64;
+        let resampler = SincFixedIn::<f32>::new(
+            resample_ratio,
+            2.0,
+            params,
+            RESAMPLE_CHUNK_FRAMES,
+            TARGET_CHANNELS,
+        )?;
+
+        Ok(Self {
+            resampler,
+            channels,
+            pending: Vec::with_capacity(RESAMPLE_CHUNK_FRAMES * 2),
+        })
+    }
+
+    /// Downmixes `input` to mono and resamples whole chunks as they become
+    /// available. Returns the mono samples (for VAD) and any newly produced
+    /// 16 kHz output.
+    fn push<T>(&mut self, input: &[T]) -> (Vec<f32>, Vec<f32>)
+    where
+        T: cpal::Sample,
+        f32: cpal::FromSample<T>,
+    {
+        let mono: Vec<f32> = input
+            .chunks(self.channels)
+            .map(|frame| {
+                let sum: f32 = frame.iter().map(|&s| f32::from_sample(s)).sum();
+                sum / self.channels as f32
+            })
+            .collect();
+
+        self.pending.extend_from_slice(&mono);
+
+        let mut resampled_out = Vec::new();
+        while self.pending.len() >= RESAMPLE_CHUNK_FRAMES {
+            let chunk: Vec<f32> = self.pending.drain(..RESAMPLE_CHUNK_FRAMES).collect();
+            if let Ok(resampled) = self.resampler.process(&[chunk], None) {
+                resampled_out.extend_from_slice(&resampled[0]);
+            }
+        }
+
+        (mono, resampled_out)
+    }
+
+    /// Resamples whatever is left in `pending` once the stream stops.
+    fn flush(&mut self) -> anyhow::Result<Vec<f32>> {
+        if self.pending.is_empty() {
+            return Ok(Vec::new());
+        }
+        let remaining = std::mem::take(&mut self.pending);
+        let resampled = self.resampler.process_partial(Some(&[remaining]), None)?;
+        Ok(resampled[0].clone())
+    }
+}
+
+/// Converts 16 kHz mono `f32` samples in `[-1.0, 1.0]` into 16-bit PCM.
+fn to_i16_samples(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// Ties a [`PcmResampler`] to a WAV file writer and the energy-based VAD
+/// stop condition used by the one-shot recorder.
+struct ResampleState<W> {
+    writer: WavWriterHandle<W>,
+    pcm: PcmResampler,
+    vad_config: VadConfig,
+    /// Contiguous silent frames seen so far, at the input sample rate.
+    silence_frames: usize,
+    /// Whether any speech has been heard yet; silence before that doesn't count.
+    speech_detected: bool,
+    silence_hangover_frames: usize,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl<W> ResampleState<W>
+where
+    W: std::io::Write + std::io::Seek,
+{
+    fn new(
+        writer: WavWriterHandle<W>,
+        input_sample_rate: u32,
+        channels: usize,
+        vad_config: VadConfig,
+        stop_flag: Arc<AtomicBool>,
+    ) -> anyhow::Result<Self> {
+        let silence_hangover_frames =
+            (vad_config.silence_hangover.as_secs_f32() * input_sample_rate as f32) as usize;
+
+        Ok(Self {
+            writer,
+            pcm: PcmResampler::new(input_sample_rate, channels)?,
+            vad_config,
+            silence_frames: 0,
+            speech_detected: false,
+            silence_hangover_frames,
+            stop_flag,
+        })
+    }
+
+    fn push<T>(&mut self, input: &[T])
+    where
+        T: cpal::Sample,
+        f32: cpal::FromSample<T>,
+    {
+        let (mono, resampled) = self.pcm.push(input);
+        self.update_vad(&mono);
+        self.write_samples(&resampled);
+    }
+
+    /// Simple energy-based VAD: once we've heard speech, stop after a
+    /// configurable run of near-silent frames.
+    fn update_vad(&mut self, mono: &[f32]) {
+        if mono.is_empty() {
+            return;
+        }
+        let rms = (mono.iter().map(|s| s * s).sum::<f32>() / mono.len() as f32).sqrt();
+
+        if rms >= self.vad_config.silence_threshold {
+            self.speech_detected = true;
+            self.silence_frames = 0;
+            return;
+        }
+
+        if !self.speech_detected {
+            return;
+        }
+
+        self.silence_frames += mono.len();
+        if self.silence_frames >= self.silence_hangover_frames {
+            self.stop_flag.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        let resampled = self.pcm.flush()?;
+        self.write_samples(&resampled);
+        Ok(())
+    }
+
+    fn write_samples(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        if let Ok(mut guard) = self.writer.try_lock() {
+            if let Some(writer) = guard.as_mut() {
+                for sample in to_i16_samples(samples) {
+                    writer.write_sample(sample).ok();
+                }
+            }
+        }
+    }
+}
+
+fn write_input_data<T, W>(input: &[T], state: &Arc<Mutex<ResampleState<W>>>)
 where
     T: cpal::Sample,
-    U: cpal::Sample + hound::Sample + cpal::FromSample<T>,
+    f32: cpal::FromSample<T>,
+    W: std::io::Write + std::io::Seek,
+{
+    if let Ok(mut state) = state.try_lock() {
+        state.push(input);
+    }
+}
+
+/// Builds an in-memory WAV (16 kHz mono 16-bit PCM) from resampled samples.
+fn wav_bytes_from_samples(samples: &[f32]) -> anyhow::Result<Vec<u8>> {
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, target_wav_spec())?;
+        for sample in to_i16_samples(samples) {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+    }
+    Ok(cursor.into_inner())
+}
+
+/// Transcribes a chunk of 16 kHz mono samples without touching disk.
+async fn transcribe_samples(
+    client: &Client<OpenAIConfig>,
+    samples: &[f32],
+    chunk_index: usize,
+) -> anyhow::Result<String> {
+    let bytes = wav_bytes_from_samples(samples)?;
+    let audio_input = AudioInput::from_vec_u8(format!("chunk-{chunk_index}.wav"), bytes);
+    let request = CreateTranscriptionRequestArgs::default()
+        .file(audio_input)
+        .model("whisper-1")
+        .build()?;
+    let response = client.audio().transcribe(request).await?;
+    Ok(response.text)
+}
+
+fn push_resampled_to_ring<T>(input: &[T], state: &Arc<Mutex<(PcmResampler, HeapProd<f32>)>>)
+where
+    T: cpal::Sample,
+    f32: cpal::FromSample<T>,
+{
+    if let Ok(mut guard) = state.try_lock() {
+        let (pcm, producer) = &mut *guard;
+        let (_, resampled) = pcm.push(input);
+        for sample in resampled {
+            producer.try_push(sample).ok();
+        }
+    }
+}
+
+/// Live dictation loop: records continuously into a ring buffer and fires
+/// off a transcription request for each rolling chunk while recording keeps
+/// going, printing each transcript as it comes back. Stops on Ctrl+C.
+async fn run_continuous_dictation(input_device: &InputDevice) -> anyhow::Result<()> {
+    let (_, device, config) = input_device.get_all();
+    let channels = config.channels() as usize;
+    let sample_format = config.sample_format();
+
+    let ring = HeapRb::<f32>::new(TARGET_SAMPLE_RATE as usize * CONTINUOUS_RING_SECONDS);
+    let (producer, mut consumer) = ring.split();
+
+    let state = Arc::new(Mutex::new((
+        PcmResampler::new(config.sample_rate().0, channels)?,
+        producer,
+    )));
+    let stream_state = state.clone();
+
+    let err_fn = |err| eprintln!("An error occurred on the audio stream: {}", err);
+
+    let stream = match sample_format {
+        cpal::SampleFormat::I8 => device.build_input_stream(
+            &config.to_owned().into(),
+            move |data, _: &_| push_resampled_to_ring::<i8>(data, &stream_state),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config.to_owned().into(),
+            move |data, _: &_| push_resampled_to_ring::<i16>(data, &stream_state),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I32 => device.build_input_stream(
+            &config.to_owned().into(),
+            move |data, _: &_| push_resampled_to_ring::<i32>(data, &stream_state),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config.to_owned().into(),
+            move |data, _: &_| push_resampled_to_ring::<f32>(data, &stream_state),
+            err_fn,
+            None,
+        )?,
+        sample_format => {
+            return Err(anyhow::anyhow!(
+                "Unsupported sample format '{sample_format}'"
+            ))
+        }
+    };
+
+    stream.play()?;
+    println!("Listening continuously, press Ctrl+C to stop...");
+
+    let client = Client::new();
+    let overlap_samples = (CONTINUOUS_OVERLAP_SECONDS * TARGET_SAMPLE_RATE as f32) as usize;
+    let mut overlap: Vec<f32> = Vec::new();
+    let mut chunk_index = 0usize;
+    let mut transcriptions = Vec::new();
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs_f32(CONTINUOUS_CHUNK_SECONDS)) => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let new_samples: Vec<f32> = std::iter::from_fn(|| consumer.try_pop()).collect();
+        if new_samples.is_empty() {
+            continue;
+        }
+
+        let mut chunk = std::mem::take(&mut overlap);
+        chunk.extend_from_slice(&new_samples);
+        overlap = new_samples[new_samples.len().saturating_sub(overlap_samples)..].to_vec();
+
+        chunk_index += 1;
+        let index = chunk_index;
+        let client = client.clone();
+        transcriptions.push(tokio::spawn(async move {
+            match transcribe_samples(&client, &chunk, index).await {
+                Ok(text) => println!("[{index}] {text}"),
+                Err(err) => eprintln!("[{index}] transcription failed: {err}"),
+            }
+        }));
+    }
+
+    drop(stream);
+    for task in transcriptions {
+        let _ = task.await;
+    }
+    Ok(())
+}
+
+/// Reads `bytes` as a WAV file, downmixes it to mono `f32`, and plays it
+/// back through `host`'s default output device. Used both as a standalone
+/// `--play` command (which needs no input device at all) and by
+/// `--loopback` to verify a recording right after capturing it.
+fn play_wav_bytes(bytes: &[u8], host: &cpal::Host) -> anyhow::Result<()> {
+    let mut reader = hound::WavReader::new(std::io::Cursor::new(bytes))?;
+    let spec = reader.spec();
+
+    let raw_samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / max))
+                .collect::<Result<_, _>>()?
+        }
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+    };
+    let mono: Vec<f32> = raw_samples
+        .chunks(spec.channels as usize)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| anyhow::anyhow!("No output device available"))?;
+    let output_config = device.default_output_config()?;
+    let channels = output_config.channels() as usize;
+    let mut stream_config = output_config.config();
+    stream_config.sample_rate = cpal::SampleRate(spec.sample_rate);
+
+    println!(
+        "Playing back {} samples at {} Hz through \"{}\"...",
+        mono.len(),
+        spec.sample_rate,
+        device.name()?
+    );
+
+    let samples = Arc::new(Mutex::new(mono.into_iter()));
+    let done = Arc::new(AtomicBool::new(false));
+    let done_clone = done.clone();
+    let err_fn = |err| eprintln!("An error occurred on the output stream: {}", err);
+
+    let stream = match output_config.sample_format() {
+        cpal::SampleFormat::I8 => device.build_output_stream(
+            &stream_config,
+            move |data, _: &_| read_output_data::<i8>(data, channels, &samples, &done_clone),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I16 => device.build_output_stream(
+            &stream_config,
+            move |data, _: &_| read_output_data::<i16>(data, channels, &samples, &done_clone),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::I32 => device.build_output_stream(
+            &stream_config,
+            move |data, _: &_| read_output_data::<i32>(data, channels, &samples, &done_clone),
+            err_fn,
+            None,
+        )?,
+        cpal::SampleFormat::F32 => device.build_output_stream(
+            &stream_config,
+            move |data, _: &_| read_output_data::<f32>(data, channels, &samples, &done_clone),
+            err_fn,
+            None,
+        )?,
+        sample_format => {
+            return Err(anyhow::anyhow!(
+                "Unsupported sample format '{sample_format}'"
+            ))
+        }
+    };
+
+    stream.play()?;
+    while !done.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    // Give the output device a moment to drain its last buffer before we tear
+    // the stream down.
+    std::thread::sleep(Duration::from_millis(200));
+    drop(stream);
+    println!("Playback complete!");
+    Ok(())
+}
+
+/// Feeds buffered mono `f32` samples to an output stream, duplicating each
+/// sample across every output channel and marking `done` once exhausted.
+fn read_output_data<T>(
+    output: &mut [T],
+    channels: usize,
+    samples: &Arc<Mutex<std::vec::IntoIter<f32>>>,
+    done: &Arc<AtomicBool>,
+) where
+    T: cpal::Sample + cpal::FromSample<f32>,
 {
-    if let Ok(mut guard) = writer.try_lock() {
-        if let Some(writer) = guard.as_mut() {
-            for &sample in input.iter() {
-                let sample: U = U::from_sample(sample);
-                writer.write_sample(sample).ok();
+    let mut samples = samples.lock().unwrap();
+    for frame in output.chunks_mut(channels) {
+        let value = match samples.next() {
+            Some(sample) => T::from_sample(sample),
+            None => {
+                done.store(true, Ordering::Relaxed);
+                T::from_sample(0.0f32)
             }
+        };
+        for out in frame.iter_mut() {
+            *out = value;
         }
     }
 }